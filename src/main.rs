@@ -1,89 +1,337 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     env,
     fs::{self, File},
     hash::{Hash, Hasher},
-    io::{stdin, ErrorKind, Write},
-    path::Path,
-    time::Instant,
+    io::{self, stdin, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use async_std::task;
 use chrono::Local;
+use crossbeam_deque::{Injector, Stealer, Worker};
+use notify::{RecursiveMode, Watcher};
 use futures::{
     channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
     future::join_all,
     StreamExt,
 };
 use serde::{Deserialize, Serialize};
-use serde_json::{map::Entry, Value};
+use serde_json::Value;
 
 const TOOL_REVISION: u32 = 1;
 
-static mut TRACE_LOG: bool = false;
-static mut SLOW_MODE: bool = false;
-static mut MANUAL_MODE: bool = false;
-static mut LOGGING: bool = false;
-
-fn tracing_enabled() -> bool {
-    unsafe { TRACE_LOG }
+/// CLI flags resolved once at startup and threaded through everywhere a
+/// global used to be read. Small and `Copy` so it's cheap to hand a copy
+/// to whichever function needs it instead of reaching for a global.
+#[derive(Clone, Copy, Default)]
+struct CrawlConfig {
+    trace: bool,
+    slow: bool,
+    manual: bool,
+    log: bool,
+    hash: bool,
+    compact: bool,
+    compress: bool,
+    watch: bool,
 }
 
-fn slow_mode_enabled() -> bool {
-    unsafe { SLOW_MODE }
+impl CrawlConfig {
+    /// Parses `env::args()` into a config, returning `None` if `-h`/`--help`
+    /// was passed so the caller can print usage and exit.
+    fn from_args() -> Option<Self> {
+        let mut config = CrawlConfig::default();
+
+        for arg in env::args() {
+            match arg.as_str() {
+                "-t" | "--trace" => config.trace = true,
+                "-s" | "--slow-mode" => config.slow = true,
+                "-m" | "--manual" => config.manual = true,
+                "-l" | "--log" => config.log = true,
+                "-H" | "--hash" => config.hash = true,
+                "-C" | "--compact" => config.compact = true,
+                "-z" | "--compress" => config.compress = true,
+                "-w" | "--watch" => config.watch = true,
+                "-h" | "--help" => return None,
+                _ => continue,
+            }
+        }
+
+        Some(config)
+    }
 }
 
-fn manual_mode() -> bool {
-    unsafe { MANUAL_MODE }
+/// Looks for `-c`/`--compare <first> <second>` among `env::args()`, returning
+/// the two record file names to diff so `main` can take the compare-mode
+/// branch instead of crawling the disk.
+fn compare_mode_files() -> Option<(String, String)> {
+    let args: Vec<String> = env::args().collect();
+    let flag_index = args
+        .iter()
+        .position(|arg| arg == "-c" || arg == "--compare")?;
+
+    Some((
+        args.get(flag_index + 1)?.clone(),
+        args.get(flag_index + 2)?.clone(),
+    ))
 }
 
-fn log_enabled() -> bool {
-    unsafe { LOGGING }
+/// Include/exclude rules loaded from `folders.json`'s `exclude` and
+/// `extensions` arrays, compiled once and shared by every worker.
+#[derive(Default, Clone)]
+struct CrawlFilters {
+    // Wildcard patterns (`*`, `?`, `**`) or, if a pattern has no wildcard
+    // characters, a plain path prefix that prunes a whole subtree.
+    excludes: Vec<String>,
+    // Case-insensitive allow-list of file extensions (without the dot).
+    // Empty means "allow everything".
+    extensions: Vec<String>,
 }
 
-fn read_args() -> Option<()> {
-    unsafe {
-        for arg in env::args() {
-            match arg.as_str() {
-                "-t" | "--trace" => TRACE_LOG = true,
-                "-s" | "--slow-mode" => SLOW_MODE = true,
-                "-m" | "--manual" => MANUAL_MODE = true,
-                "-l" | "--log" => LOGGING = true,
-                "-h" | "--help" => return None,
-                _ => continue,
+impl CrawlFilters {
+    fn is_excluded(&self, path: &str) -> bool {
+        // Patterns in folders.json are written with `/` (`**/node_modules`)
+        // but every path on this Windows-only tool is built with `\`, so
+        // normalize both sides to `/` before comparing either way.
+        let path = normalize_separators(path);
+
+        self.excludes.iter().any(|pattern| {
+            let pattern = normalize_separators(pattern);
+
+            if pattern.contains('*') || pattern.contains('?') {
+                if pattern.contains('/') {
+                    glob_match(&pattern, &path)
+                } else {
+                    // gitignore semantics: a pattern with no separator (e.g.
+                    // `*.tmp`) isn't anchored to the root, so it matches
+                    // against any single path segment instead of requiring
+                    // the whole path to be one segment long.
+                    path.split('/').any(|segment| segment_match(&pattern, segment))
+                }
+            } else {
+                is_path_prefix(&path, &pattern)
             }
+        })
+    }
+
+    fn extension_allowed(&self, path: &str) -> bool {
+        if self.extensions.is_empty() {
+            return true;
+        }
+
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => self
+                .extensions
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(ext)),
+            None => false,
         }
     }
+}
 
-    Some(())
+/// Rewrites `\` to `/` so patterns written with forward slashes (as every
+/// example in folders.json uses) still match paths built with
+/// `Path::join`/string concatenation on this Windows-only tool.
+fn normalize_separators(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Whether `pattern` is a whole-segment prefix of `path`, i.e. `pattern`
+/// matches up to a `/` boundary rather than partway into a segment, so
+/// excluding `C:/foo` prunes `C:/foo/bar` but not `C:/foobar` or `C:/foo.txt`.
+fn is_path_prefix(path: &str, pattern: &str) -> bool {
+    path.strip_prefix(pattern)
+        .map(|rest| rest.is_empty() || rest.starts_with('/'))
+        .unwrap_or(false)
+}
+
+/// Shell-style glob matcher over `/`-separated segments: `?` matches exactly
+/// one character, `*` matches any run of characters within a single segment
+/// (it never crosses a `/`), and `**` matches zero or more whole segments so
+/// a pattern such as `**/node_modules` matches at any depth.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern_segments: Vec<&str> = pattern.split('/').collect();
+    let text_segments: Vec<&str> = text.split('/').collect();
+
+    segments_match(&pattern_segments, &text_segments)
+}
+
+fn segments_match(pattern: &[&str], text: &[&str]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(&"**") => (0..=text.len()).any(|skip| segments_match(&pattern[1..], &text[skip..])),
+        Some(segment) => {
+            !text.is_empty() && segment_match(segment, text[0]) && segments_match(&pattern[1..], &text[1..])
+        }
+    }
+}
+
+/// Matches a single path segment against a pattern segment that may contain
+/// `*` (any run of characters) and `?` (exactly one character).
+fn segment_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let mut p = 0;
+    let mut t = 0;
+    let mut star_p = None;
+    let mut star_t = 0;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star_p = Some(p);
+            star_t = t;
+            p += 1;
+        } else if let Some(sp) = star_p {
+            p = sp + 1;
+            star_t += 1;
+            t = star_t;
+        } else {
+            return false;
+        }
+    }
+
+    while p < pattern.len() && pattern[p] == '*' {
+        p += 1;
+    }
+
+    p == pattern.len()
+}
+
+/// Shared directory-job queue feeding the crawl worker pool.
+///
+/// Every worker owns a local `Worker<String>` deque, but jobs discovered
+/// while walking a directory (its subfolders) are pushed onto the global
+/// `Injector` so idle workers can steal from busier ones instead of
+/// starving while a single deep tree is being walked by one worker.
+struct CrawlQueue {
+    injector: Injector<String>,
+    stealers: Vec<Stealer<String>>,
+    outstanding_jobs: AtomicUsize,
+    // Total directory jobs ever pushed, including the initial roots. Unlike
+    // `outstanding_jobs` this never decreases, so it's what progress
+    // reporting uses as "directories discovered".
+    total_jobs: AtomicUsize,
+    visited: Mutex<HashSet<PathBuf>>,
+    filters: CrawlFilters,
+    config: CrawlConfig,
+    cancelled: AtomicBool,
+}
+
+impl CrawlQueue {
+    fn new(stealers: Vec<Stealer<String>>, filters: CrawlFilters, config: CrawlConfig) -> Self {
+        CrawlQueue {
+            injector: Injector::new(),
+            stealers,
+            outstanding_jobs: AtomicUsize::new(0),
+            total_jobs: AtomicUsize::new(0),
+            visited: Mutex::new(HashSet::new()),
+            filters,
+            config,
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    /// Pushes a directory job, marking it as outstanding first so idle
+    /// workers never observe a false "crawl complete" while it's in flight.
+    fn push(&self, path: String) {
+        self.outstanding_jobs.fetch_add(1, Ordering::SeqCst);
+        self.total_jobs.fetch_add(1, Ordering::SeqCst);
+        self.injector.push(path);
+    }
+
+    /// Marks the job that was just processed as done, returning the
+    /// number of directory jobs still outstanding.
+    fn job_done(&self) -> usize {
+        self.outstanding_jobs.fetch_sub(1, Ordering::SeqCst) - 1
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.outstanding_jobs.load(Ordering::SeqCst) == 0
+    }
+
+    /// Registers `path` as visited if it wasn't already, returning `true`
+    /// if this call was the one to insert it (i.e. it's safe to crawl).
+    /// Guards against symlink cycles by keying on the canonicalized path.
+    fn mark_visited(&self, path: &Path) -> bool {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        self.visited.lock().unwrap().insert(canonical)
+    }
+
+    /// Requests that the crawl wind down: workers stop pushing new
+    /// directory jobs, so the queue drains and `is_exhausted` eventually
+    /// becomes true on its own without losing any job that's in flight.
+    fn request_cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Pops the next job for `local`, stealing from the global injector or a
+/// sibling worker if the local deque is empty. Standard crossbeam-deque
+/// work-stealing loop: retry until either a task is found or every
+/// source reports empty.
+fn find_task(local: &Worker<String>, queue: &CrawlQueue) -> Option<String> {
+    local.pop().or_else(|| {
+        std::iter::repeat_with(|| {
+            queue
+                .injector
+                .steal_batch_and_pop(local)
+                .or_else(|| queue.stealers.iter().map(|s| s.steal()).collect())
+        })
+        .find(|s| !s.is_retry())
+        .and_then(|s| s.success())
+    })
 }
 
 #[async_std::main]
 async fn main() {
-    if let None = read_args() {
-        println!("-t or --trace to print what the tool is doing");
-        println!("-s or --slow-mode is useful if you have a slow cpu/hard drive so the tool won't take all the ressources available but of course will be slower");
-        println!("-m or --manual analyses the folders specified inside folders.json instead of the most common folders used by applications/installers");
-        println!("-h or --help you just used it");
+    let config = match CrawlConfig::from_args() {
+        Some(config) => config,
+        None => {
+            println!("-t or --trace to print what the tool is doing");
+            println!("-s or --slow-mode is useful if you have a slow cpu/hard drive so the tool won't take all the ressources available but of course will be slower");
+            println!("-m or --manual analyses the folders specified inside folders.json instead of the most common folders used by applications/installers");
+            println!("-H or --hash computes a BLAKE3 content hash for every file so in-place edits that don't change file size are still detected");
+            println!("-C or --compact writes minified JSON instead of pretty-printed JSON");
+            println!("-z or --compress wraps the output file in a zstd stream (.json.zst)");
+            println!("-w or --watch keeps running after the initial crawl and records changes as they happen");
+            println!("-c or --compare <first> <second> diffs two existing record files instead of scanning the disk");
+            println!("-h or --help you just used it");
+
+            return;
+        }
+    };
+
+    if let Some((first_file, second_file)) = compare_mode_files() {
+        let start_instant = Instant::now();
+        compare_analysis(&first_file, &second_file, config).await;
+        println!("Time : {}ms", start_instant.elapsed().as_millis());
 
         return;
     }
 
-    let start_instant = Instant::now();
-    compare_analysis(
-        "record_2021-11-07_01-47-59.json",
-        "record_2021-11-07_01-48-00.json",
-    )
-    .await;
-    println!("Time : {}ms", start_instant.elapsed().as_millis());
-    return;
-
     let folders: HashSet<String>;
+    let filters: CrawlFilters;
 
-    if manual_mode() {
+    if config.manual {
         println!("Manual scan");
 
-        folders = load_manual_mode_folders();
+        let manual_mode_folders = load_manual_mode_folders();
+        folders = manual_mode_folders.0;
+        filters = manual_mode_folders.1;
 
         if folders.len() == 0 {
             println!("No folders specified");
@@ -94,51 +342,68 @@ async fn main() {
         println!("Auto scan");
 
         folders = load_auto_mode_folders();
+        filters = CrawlFilters::default();
     }
 
     let start_instant = Instant::now();
 
-    let (tx, rx) = mpsc::unbounded::<RecorderSignal>();
-
-    let recorder_work = task::spawn(file_recorder(rx, folders.len()));
-
-    if slow_mode_enabled() {
-        println!("Slow mode");
-
-        for path in folders {
-            read_path(path, tx.clone()).await;
-        }
-    } else {
-        println!("Fast mode");
-
-        join_all(
-            folders
-                .into_iter()
-                .map(|path| task::spawn(read_path(path, tx.clone()))),
-        )
+    let watch_filters = filters.clone();
+    let crawl = CrawlJob::new(config)
+        .spawn(&folders, filters)
+        .run_with_progress()
         .await;
-    }
-    let _ = tx.unbounded_send(RecorderSignal::Close);
-    recorder_work.await;
 
     println!("Time : {}ms", start_instant.elapsed().as_millis());
 
+    if config.watch {
+        println!("Watch mode");
+        watch_mode(folders, crawl.entries_info, watch_filters, config).await;
+    }
+
     println!("Press Enter to exit");
     let mut buff = String::new();
     let _ = stdin().read_line(&mut buff);
 }
 
-fn load_manual_mode_folders() -> HashSet<String> {
+fn load_manual_mode_folders() -> (HashSet<String>, CrawlFilters) {
     let target_folders: Value =
         serde_json::from_str(&fs::read_to_string("folders.json").expect("JSON file doesn't exist"))
             .expect("Malformated JSON");
 
-    HashSet::from_iter(
+    let folders = HashSet::from_iter(
         target_folders["folders"]
             .as_array()
             .expect("No folders found in JSON")
             .iter()
-            .map(|v| v.to_string()),
+            .filter_map(|v| v.as_str().map(str::to_owned)),
+    );
+
+    let excludes = target_folders["exclude"]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_owned))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let extensions = target_folders["extensions"]
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str().map(|ext| ext.trim_start_matches('.').to_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    (
+        folders,
+        CrawlFilters {
+            excludes,
+            extensions,
+        },
     )
 }
 
@@ -167,7 +432,7 @@ fn load_auto_mode_folders() -> HashSet<String> {
     ])
 }
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 enum EntryType {
     File,
     Directory,
@@ -183,6 +448,12 @@ struct EntryInfo {
     #[serde(rename = "Octets")]
     #[serde(skip_serializing_if = "octets_is_zero")]
     octets: u64,
+    // Only populated for files when -H/--hash is passed, so this stays
+    // empty (and absent from the JSON) for directories and unhashed runs.
+    #[serde(rename = "Hash")]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
+    hash: String,
 }
 
 impl Hash for EntryInfo {
@@ -190,6 +461,7 @@ impl Hash for EntryInfo {
         self.entry_type.hash(state);
         self.path.hash(state);
         self.octets.hash(state);
+        self.hash.hash(state);
     }
 }
 
@@ -203,6 +475,12 @@ struct Crawl {
     entry_count: usize,
     #[serde(rename = "Entries")]
     entries_info: HashSet<EntryInfo>,
+    // Set when a Ctrl-C cancellation cut the crawl short; the record holds
+    // what was captured before the cutoff.
+    #[serde(rename = "Partial")]
+    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default)]
+    partial: bool,
 }
 
 impl PartialEq for Crawl {
@@ -211,6 +489,7 @@ impl PartialEq for Crawl {
         self.used_tool_revision == other.used_tool_revision
             && self.entry_count == other.entry_count
             && self.entries_info == other.entries_info
+            && self.partial == other.partial
     }
 }
 
@@ -219,7 +498,180 @@ enum RecorderSignal {
     Close,
 }
 
-#[derive(Serialize)]
+/// A structured snapshot of crawl progress, sent to the job-report channel
+/// every time a directory job finishes so the caller can drive a live
+/// progress line instead of guessing from `-t`/`--trace` output.
+#[derive(Clone, Copy, Default)]
+struct CrawlProgress {
+    dirs_discovered: usize,
+    dirs_completed: usize,
+    entries_recorded: usize,
+    bytes_recorded: u64,
+    elapsed: Duration,
+}
+
+impl CrawlProgress {
+    /// Projects remaining time from the completion rate observed so far.
+    /// `None` until at least one directory job has finished.
+    fn eta(&self) -> Option<Duration> {
+        if self.dirs_completed == 0 {
+            return None;
+        }
+
+        let remaining = self.dirs_discovered.saturating_sub(self.dirs_completed);
+
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+
+        let seconds_per_dir = self.elapsed.as_secs_f64() / self.dirs_completed as f64;
+
+        Some(Duration::from_secs_f64(seconds_per_dir * remaining as f64))
+    }
+}
+
+/// Builder that owns a resolved `CrawlConfig` and spawns the worker pool,
+/// the recorder, and the Ctrl-C cancellation plumbing for one crawl,
+/// returning a `CrawlHandle` the caller drives to completion.
+struct CrawlJob {
+    config: CrawlConfig,
+}
+
+impl CrawlJob {
+    fn new(config: CrawlConfig) -> Self {
+        CrawlJob { config }
+    }
+
+    fn spawn(&self, folders: &HashSet<String>, filters: CrawlFilters) -> CrawlHandle {
+        let worker_count = if self.config.slow {
+            println!("Slow mode");
+
+            1
+        } else {
+            println!("Fast mode");
+
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4)
+        };
+
+        let workers: Vec<Worker<String>> = (0..worker_count).map(|_| Worker::new_fifo()).collect();
+        let queue = Arc::new(CrawlQueue::new(
+            workers.iter().map(Worker::stealer).collect(),
+            filters,
+            self.config,
+        ));
+
+        for path in folders {
+            if queue.mark_visited(Path::new(path)) {
+                queue.push(path.clone());
+            }
+        }
+
+        // A single process-wide Ctrl-C handler: stop handing out new
+        // directory jobs so the queue drains and the recorder flushes
+        // whatever was recorded so far, clearly marked as partial.
+        let cancel_queue = Arc::clone(&queue);
+        if let Err(err) = ctrlc::set_handler(move || {
+            if !cancel_queue.is_cancelled() {
+                println!(
+                    "\nCancelling: finishing in-flight directory jobs and flushing a partial record"
+                );
+            }
+
+            cancel_queue.request_cancel();
+        }) {
+            println!(
+                "Couldn't register the Ctrl-C handler ({}), cancellation won't flush a partial record",
+                err
+            );
+        }
+
+        let (tx, rx) = mpsc::unbounded::<RecorderSignal>();
+        let (progress_tx, progress_rx) = mpsc::unbounded::<CrawlProgress>();
+
+        let start_instant = Instant::now();
+        let recorder_work = task::spawn(file_recorder(
+            rx,
+            Arc::clone(&queue),
+            progress_tx,
+            start_instant,
+        ));
+
+        let completion = task::spawn(async move {
+            join_all(
+                workers
+                    .into_iter()
+                    .map(|worker| task::spawn(crawl_worker(worker, Arc::clone(&queue), tx.clone()))),
+            )
+            .await;
+
+            let _ = tx.unbounded_send(RecorderSignal::Close);
+            recorder_work.await
+        });
+
+        CrawlHandle {
+            progress: progress_rx,
+            completion,
+        }
+    }
+}
+
+/// Handle to a running crawl: carries a live progress stream and resolves
+/// to the finished (possibly partial) `Crawl` once the recorder closes it.
+struct CrawlHandle {
+    progress: UnboundedReceiver<CrawlProgress>,
+    completion: task::JoinHandle<Crawl>,
+}
+
+impl CrawlHandle {
+    /// Prints a live-updating progress line for every report until the
+    /// recorder closes the channel, then awaits the final `Crawl`.
+    async fn run_with_progress(mut self) -> Crawl {
+        while let Some(progress) = self.progress.next().await {
+            print_progress_line(&progress);
+        }
+
+        println!();
+
+        self.completion.await
+    }
+}
+
+fn print_progress_line(progress: &CrawlProgress) {
+    let eta = match progress.eta() {
+        Some(eta) => format!("{}s", eta.as_secs()),
+        None => "?".to_owned(),
+    };
+
+    print!(
+        "\r{}/{} dirs, {} entries, {} recorded, {}s elapsed, ETA {}    ",
+        progress.dirs_completed,
+        progress.dirs_discovered,
+        progress.entries_recorded,
+        format_bytes(progress.bytes_recorded),
+        progress.elapsed.as_secs(),
+        eta,
+    );
+
+    let _ = io::stdout().flush();
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1}{}", size, UNITS[unit])
+}
+
+#[derive(Serialize, Clone)]
 enum EntryDifferenceType {
     New,
     Removed,
@@ -227,6 +679,14 @@ enum EntryDifferenceType {
     NoChange,
 }
 
+// The u64 field below only ever stores a magnitude, so the sign is carried
+// separately.
+#[derive(Serialize)]
+enum OctetsDirection {
+    Increase,
+    Decrease,
+}
+
 #[derive(Serialize)]
 struct EntryDifference<'a> {
     #[serde(rename = "Type")]
@@ -239,12 +699,25 @@ struct EntryDifference<'a> {
     #[serde(rename = "OctetsDifference")]
     #[serde(skip_serializing_if = "octets_is_zero")]
     octets_difference: u64,
+    #[serde(rename = "Direction")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    direction: Option<OctetsDirection>,
+    // Set when both crawls were made with -H/--hash and the content hash
+    // changed, whether or not the size did, so a config file edited in
+    // place still shows up.
+    #[serde(rename = "HashChanged")]
+    #[serde(skip_serializing_if = "is_false")]
+    hash_changed: bool,
 }
 
 fn octets_is_zero(diff: &u64) -> bool {
     *diff == 0
 }
 
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
 #[derive(Serialize)]
 struct DifferenceAnalysis<'a> {
     #[serde(rename = "DateTime")]
@@ -253,98 +726,201 @@ struct DifferenceAnalysis<'a> {
     entries_difference: Vec<EntryDifference<'a>>,
 }
 
-async fn compare_analysis(first_file: &str, second_file: &str) {
-    let first_analysis =
-        serde_json::from_slice::<Crawl>(fs::read(first_file).unwrap().as_slice()).unwrap();
-
-    let second_analysis =
-        serde_json::from_slice::<Crawl>(fs::read(second_file).unwrap().as_slice()).unwrap();
-
-    // get the dates and compare the oldest it with the newest
+/// Parses a `Crawl::date_time` (`record_*`/`analysis_*` use the same
+/// `%F_%H-%M-%S` format) so crawls can be ordered chronologically.
+fn parse_crawl_datetime(date_time: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(date_time, "%F_%H-%M-%S").ok()
+}
 
-    // const core_count: usize = 24;
+/// Loads a `Crawl` record, transparently decoding it if `path` ends in
+/// `.zst` (as produced by `-z`/`--compress`).
+fn load_crawl(path: &str) -> Crawl {
+    let file = File::open(path).expect("Couldn't open crawl file");
 
-    // if first_analysis.entry_count >= core_count {
-    //     let sub_task_count = first_analysis.entry_count / core_count;
-    //     let sub_task_count_rest = first_analysis.entry_count % core_count;
+    if path.ends_with(".zst") {
+        let decoder = zstd::Decoder::new(file).expect("Couldn't create zstd decoder");
+        serde_json::from_reader(decoder).expect("Malformated JSON")
+    } else {
+        serde_json::from_reader(file).expect("Malformated JSON")
+    }
+}
 
-    //     let mut tasks = Vec::with_capacity(core_count);
+/// Serializes `value` as JSON directly into `writer`, so the whole
+/// document never has to be held in memory as a `String` first. Honors
+/// `-C`/`--compact` for minified vs pretty-printed output.
+fn serialize_json<T: Serialize, W: Write>(
+    writer: W,
+    value: &T,
+    config: CrawlConfig,
+) -> serde_json::Result<()> {
+    if config.compact {
+        serde_json::to_writer(writer, value)
+    } else {
+        serde_json::to_writer_pretty(writer, value)
+    }
+}
 
-    //     let entries_vec: Vec<EntryInfo> = first_analysis.entries_info.into_iter().collect();
+/// Creates `file_name` and streams `value` into it as JSON, wrapping the
+/// writer in a zstd encoder when `-z`/`--compress` is set.
+fn write_json_to_file<T: Serialize>(file_name: &str, value: &T, config: CrawlConfig) {
+    let file = match File::create(file_name) {
+        Ok(file) => file,
+        Err(_) => {
+            println!("Couldn't create the result file");
+            return;
+        }
+    };
 
-    //     let mut step = 0usize;
+    let write_result = if config.compress {
+        match zstd::Encoder::new(file, 0) {
+            Ok(encoder) => serialize_json(&mut encoder.auto_finish(), value, config),
+            Err(_) => {
+                println!("Couldn't create the zstd encoder");
+                return;
+            }
+        }
+    } else {
+        serialize_json(file, value, config)
+    };
 
-    //     for core in 0..core_count {
-    //         let range: usize;
+    if write_result.is_err() {
+        println!("Couldn't write into {}", file_name);
+    }
+}
 
-    //         if core == core_count - 1 {
-    //             range = sub_task_count + sub_task_count_rest;
-    //         } else {
-    //             range = sub_task_count;
-    //         }
+/// Appends `.zst` to `file_name` when `-z`/`--compress` is set.
+fn compressed_file_name(file_name: String, config: CrawlConfig) -> String {
+    if config.compress {
+        format!("{}.zst", file_name)
+    } else {
+        file_name
+    }
+}
 
-    //         let sub_tasks = entries_vec[step..step + range].to_vec();
+async fn compare_analysis(first_file: &str, second_file: &str, config: CrawlConfig) {
+    let first_analysis = load_crawl(first_file);
 
-    //         tasks.push(task::spawn(async move { for entry in sub_tasks {} }));
+    let second_analysis = load_crawl(second_file);
 
-    //         step += range;
-    //     }
+    // Figure out which crawl is actually older so the report reads
+    // old -> new regardless of the order the files were passed in.
+    let first_is_older = match (
+        parse_crawl_datetime(&first_analysis.date_time),
+        parse_crawl_datetime(&second_analysis.date_time),
+    ) {
+        (Some(first_date), Some(second_date)) => first_date <= second_date,
+        _ => true,
+    };
 
-    //     join_all(tasks).await;
-    // }
+    let (older_analysis, newer_analysis) = if first_is_older {
+        (&first_analysis, &second_analysis)
+    } else {
+        (&second_analysis, &first_analysis)
+    };
 
-    let entries_not_changed = first_analysis
+    let older_by_path: HashMap<&str, &EntryInfo> = older_analysis
         .entries_info
-        .intersection(&second_analysis.entries_info);
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry))
+        .collect();
 
-    let entries_changed = first_analysis
+    let newer_by_path: HashMap<&str, &EntryInfo> = newer_analysis
         .entries_info
-        .difference(&second_analysis.entries_info);
+        .iter()
+        .map(|entry| (entry.path.as_str(), entry))
+        .collect();
 
     let mut difference_analysis = Vec::new();
 
-    for entry in entries_not_changed {
+    for (path, new_entry) in newer_by_path.iter() {
+        let Some(old_entry) = older_by_path.get(path) else {
+            difference_analysis.push(EntryDifference {
+                entry_type: &new_entry.entry_type,
+                difference_type: EntryDifferenceType::New,
+                path: Some(*path),
+                octets_difference: new_entry.octets,
+                direction: None,
+                hash_changed: false,
+            });
+
+            continue;
+        };
+
+        let octets_changed = old_entry.octets != new_entry.octets;
+        let hash_changed = !old_entry.hash.is_empty()
+            && !new_entry.hash.is_empty()
+            && old_entry.hash != new_entry.hash;
+
+        if !octets_changed && !hash_changed {
+            difference_analysis.push(EntryDifference {
+                entry_type: &new_entry.entry_type,
+                difference_type: EntryDifferenceType::NoChange,
+                path: Some(*path),
+                octets_difference: 0,
+                direction: None,
+                hash_changed: false,
+            });
+
+            continue;
+        }
+
+        let (octets_difference, direction) = if new_entry.octets >= old_entry.octets {
+            (
+                new_entry.octets - old_entry.octets,
+                Some(OctetsDirection::Increase),
+            )
+        } else {
+            (
+                old_entry.octets - new_entry.octets,
+                Some(OctetsDirection::Decrease),
+            )
+        };
+
         difference_analysis.push(EntryDifference {
-            entry_type: &entry.entry_type,
-            difference_type: EntryDifferenceType::NoChange,
-            path: Some(&entry.path),
-            octets_difference: 0,
+            entry_type: &new_entry.entry_type,
+            difference_type: EntryDifferenceType::SizeChange,
+            path: Some(*path),
+            octets_difference,
+            direction: if octets_changed { direction } else { None },
+            hash_changed,
         });
     }
 
-    for entry in entries_changed {
+    for (path, old_entry) in older_by_path.iter() {
+        if newer_by_path.contains_key(path) {
+            continue;
+        }
+
         difference_analysis.push(EntryDifference {
-            entry_type: &entry.entry_type,
-            difference_type: EntryDifferenceType::New,
-            path: Some(&entry.path),
-            octets_difference: entry.octets,
+            entry_type: &old_entry.entry_type,
+            difference_type: EntryDifferenceType::Removed,
+            path: None,
+            octets_difference: old_entry.octets,
+            direction: None,
+            hash_changed: false,
         });
     }
 
     let date_time = Local::now().format("%F_%H-%M-%S").to_string();
-
-    let mut analysis_file = match File::create(format!("analysis_{}.json", &date_time)) {
-        Ok(file) => file,
-        Err(_) => {
-            println!("Couldn't create the result file");
-            return;
-        }
-    };
+    let analysis_file_name =
+        compressed_file_name(format!("analysis_{}.json", &date_time), config);
 
     let analysis = DifferenceAnalysis {
         date_time,
         entries_difference: difference_analysis,
     };
 
-    if let Ok(analysis_json) = serde_json::to_string_pretty(&analysis) {
-        if let Err(_) = analysis_file.write_all(analysis_json.as_bytes()) {
-            println!("Couldn't write into analysis file");
-        }
-    }
+    write_json_to_file(&analysis_file_name, &analysis, config);
 }
 
-async fn file_recorder(mut receiver: UnboundedReceiver<RecorderSignal>, jobs_working: usize) {
+async fn file_recorder(
+    mut receiver: UnboundedReceiver<RecorderSignal>,
+    queue: Arc<CrawlQueue>,
+    mut progress: UnboundedSender<CrawlProgress>,
+    start_instant: Instant,
+) -> Crawl {
     let mut jobs_done = 0usize;
+    let mut bytes_recorded = 0u64;
 
     let mut entries_info: HashSet<EntryInfo> = HashSet::new();
 
@@ -352,54 +928,113 @@ async fn file_recorder(mut receiver: UnboundedReceiver<RecorderSignal>, jobs_wor
         match signal {
             RecorderSignal::EntriesVec(entries) => {
                 for entry in entries.into_iter() {
+                    bytes_recorded += entry.octets;
                     entries_info.insert(entry);
                 }
 
                 jobs_done += 1;
 
-                if tracing_enabled() {
-                    println!("{} out of {} job done", jobs_done, jobs_working);
+                if queue.config.trace {
+                    println!(
+                        "{} directory jobs done, {} outstanding",
+                        jobs_done,
+                        queue.outstanding_jobs.load(Ordering::SeqCst)
+                    );
                 }
 
-                if jobs_done == jobs_working {
-                    break;
-                }
+                let _ = progress.unbounded_send(CrawlProgress {
+                    dirs_discovered: queue.total_jobs.load(Ordering::SeqCst),
+                    dirs_completed: jobs_done,
+                    entries_recorded: entries_info.len(),
+                    bytes_recorded,
+                    elapsed: start_instant.elapsed(),
+                });
+
+                // Don't treat `outstanding_jobs == 0` as "done": with more than
+                // one worker, the job that drives the counter to zero isn't
+                // necessarily the last `EntriesVec` to arrive on this channel,
+                // since `job_done` races `unbounded_send` across workers. Only
+                // `RecorderSignal::Close`, sent once every worker has exited
+                // and every send has happened-before it, is a trustworthy
+                // completion signal.
             }
             RecorderSignal::Close => receiver.close(),
         }
     }
 
-    if tracing_enabled() {
+    if queue.config.trace {
         println!("All jobs done droping the receiver");
     }
     drop(receiver);
+    drop(progress);
 
     let date_time = Local::now().format("%F_%H-%M-%S").to_string();
-
-    let mut crawl_file = match File::create(format!("record_{}.json", &date_time)) {
-        Ok(file) => file,
-        Err(_) => {
-            println!("Couldn't create the result file");
-            return;
-        }
-    };
+    let crawl_file_name = compressed_file_name(format!("record_{}.json", &date_time), queue.config);
 
     let crawl = Crawl {
         date_time,
         used_tool_revision: TOOL_REVISION,
         entry_count: entries_info.len(),
         entries_info,
+        partial: queue.is_cancelled(),
     };
 
-    if let Ok(crawl_json) = serde_json::to_string_pretty(&crawl) {
-        if let Err(_) = crawl_file.write_all(crawl_json.as_bytes()) {
-            println!("Couldn't write into record file");
+    write_json_to_file(&crawl_file_name, &crawl, queue.config);
+
+    crawl
+}
+
+/// Runs one worker of the crawl pool: pull directory jobs from `queue`
+/// (local deque first, then work-stealing from the injector/siblings)
+/// until no jobs remain anywhere and none are outstanding.
+async fn crawl_worker(
+    local: Worker<String>,
+    queue: Arc<CrawlQueue>,
+    sender: UnboundedSender<RecorderSignal>,
+) {
+    loop {
+        match find_task(&local, &queue) {
+            Some(path) => scan_dir(path, &sender, &queue).await,
+            None => {
+                if queue.is_exhausted() {
+                    break;
+                }
+
+                task::yield_now().await;
+            }
+        }
+    }
+
+    drop(sender);
+}
+
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Streams `path` through BLAKE3 in fixed-size chunks so hashing a large
+/// file doesn't require reading it into memory all at once.
+fn hash_file_contents(path: &Path) -> Option<String> {
+    let mut file = File::open(path).ok()?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = [0u8; HASH_CHUNK_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+
+        if read == 0 {
+            break;
         }
+
+        hasher.update(&buffer[..read]);
     }
+
+    Some(hasher.finalize().to_hex().to_string())
 }
 
-async fn read_path(path: String, sender: UnboundedSender<RecorderSignal>) {
-    if log_enabled() {
+/// Reads a single directory level, emitting an `EntryInfo` per entry and
+/// pushing any subdirectories back onto `queue` for another worker to pick
+/// up. Symlink cycles are pruned via `queue.mark_visited`.
+async fn scan_dir(path: String, sender: &UnboundedSender<RecorderSignal>, queue: &Arc<CrawlQueue>) {
+    if queue.config.log {
         println!("Analysing {}", path);
     }
 
@@ -413,6 +1048,10 @@ async fn read_path(path: String, sender: UnboundedSender<RecorderSignal>) {
                 _ => println!("Error {} : {}", err.to_string(), path),
             }
 
+            queue.job_done();
+
+            // Still report the finished job so progress counts it as completed.
+            let _ = sender.unbounded_send(RecorderSignal::EntriesVec(Box::new(Vec::new())));
             return;
         }
     };
@@ -425,6 +1064,18 @@ async fn read_path(path: String, sender: UnboundedSender<RecorderSignal>) {
             Err(_) => continue,
         };
 
+        let path = entry.path().to_str().unwrap_or("Invalid path").to_owned();
+
+        // Check the exclude patterns before touching the filesystem any
+        // further, so excluded subtrees never get stat-ed.
+        if queue.filters.is_excluded(&path) {
+            if queue.config.log {
+                println!("EXCLUDED: {}", path);
+            }
+
+            continue;
+        }
+
         if !entry.path().exists() {
             continue;
         }
@@ -434,39 +1085,61 @@ async fn read_path(path: String, sender: UnboundedSender<RecorderSignal>) {
         if entry.path().is_file() {
             entry_type = EntryType::File;
 
-            if log_enabled() {
+            if !queue.filters.extension_allowed(&path) {
+                continue;
+            }
+
+            if queue.config.log {
                 println!("FILE: {}", entry.path().display());
             }
         } else if entry.path().is_dir() {
             entry_type = EntryType::Directory;
 
-            if log_enabled() {
+            if queue.config.log {
                 println!("DIRECTORY: {}", entry.path().display());
             }
+
+            // Only descend into subdirectories we haven't visited yet
+            // (guarding against symlink cycles) and only while the crawl
+            // hasn't been cancelled, so a Ctrl-C stops handing out new
+            // jobs instead of chasing the tree to the bottom.
+            if !queue.is_cancelled() && queue.mark_visited(&entry.path()) {
+                queue.push(path.clone());
+            }
         } else {
             entry_type = EntryType::Unknown;
 
-            if log_enabled() {
+            if queue.config.log {
                 println!("UNKNOWN: {}", entry.path().display());
             }
         }
 
-        let path = entry.path().to_str().unwrap_or("Invalid path").to_owned();
         let octets: u64 = match entry.metadata() {
             Ok(metadata) => metadata.len(),
             Err(_) => 0,
         };
 
+        let hash = if queue.config.hash && entry_type == EntryType::File {
+            hash_file_contents(&entry.path()).unwrap_or_default()
+        } else {
+            String::new()
+        };
+
         let ent = EntryInfo {
             entry_type,
             path,
             octets,
+            hash,
         };
 
         entries_info.push(ent);
     }
 
-    if tracing_enabled() {
+    // Mark this job done only after queuing subdirectories, so the
+    // outstanding count never dips to zero while work is still in flight.
+    queue.job_done();
+
+    if queue.config.trace {
         println!("Sending {} SIGNAL", path);
     }
 
@@ -475,6 +1148,364 @@ async fn read_path(path: String, sender: UnboundedSender<RecorderSignal>) {
             "Couldn't process due to system error (make sure you have enough memory available)"
         );
     }
+}
 
-    drop(sender);
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+const WATCH_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+const WATCH_RESCAN_INTERVAL: Duration = Duration::from_secs(30);
+
+// Owned counterpart to `EntryDifference`, used by watch mode since the
+// rolling log outlives any single re-stat and can't borrow from it.
+#[derive(Serialize, Clone)]
+struct WatchDifference {
+    #[serde(rename = "Type")]
+    entry_type: EntryType,
+    #[serde(rename = "DifferenceType")]
+    difference_type: EntryDifferenceType,
+    #[serde(rename = "Path")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(rename = "OctetsDifference")]
+    #[serde(skip_serializing_if = "octets_is_zero")]
+    octets_difference: u64,
+}
+
+#[derive(Serialize)]
+struct WatchLog {
+    #[serde(rename = "DateTime")]
+    date_time: String,
+    #[serde(rename = "EntriesDifference")]
+    entries_difference: Vec<WatchDifference>,
+}
+
+/// Re-reads a single path's metadata, matching the `EntryInfo` schema a
+/// full crawl would produce. Returns `None` if the path no longer exists.
+fn restat_entry(path: &str, config: CrawlConfig) -> Option<EntryInfo> {
+    let metadata = fs::metadata(path).ok()?;
+
+    let entry_type = if metadata.is_file() {
+        EntryType::File
+    } else if metadata.is_dir() {
+        EntryType::Directory
+    } else {
+        EntryType::Unknown
+    };
+
+    let hash = if config.hash && entry_type == EntryType::File {
+        hash_file_contents(Path::new(path)).unwrap_or_default()
+    } else {
+        String::new()
+    };
+
+    Some(EntryInfo {
+        entry_type,
+        path: path.to_owned(),
+        octets: metadata.len(),
+        hash,
+    })
+}
+
+/// Whether a raw OS-watcher event path should be reconciled at all, applying
+/// the same `CrawlFilters` a full crawl or `rescan_subtree` would: excluded
+/// paths never reach `reconcile_path`, and the extension allow-list applies
+/// once the path is known to be a file (directories always pass it).
+fn event_path_allowed(path: &str, filters: &CrawlFilters) -> bool {
+    if filters.is_excluded(path) {
+        return false;
+    }
+
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => filters.extension_allowed(path),
+        _ => true,
+    }
+}
+
+/// Diffs the current state of `path` against `live_entries`, pushing a
+/// `WatchDifference` onto `log` for anything that actually changed and
+/// updating `live_entries` to match. Because this always re-stats the
+/// path instead of reacting to the raw event kind, a "create" quickly
+/// followed by "modify" events collapses into a single `New` carrying the
+/// file's final size, since by the time the debounce window elapses the
+/// write has already landed.
+fn reconcile_path(
+    path: &str,
+    config: CrawlConfig,
+    live_entries: &mut HashMap<String, EntryInfo>,
+    log: &mut Vec<WatchDifference>,
+) {
+    match restat_entry(path, config) {
+        None => {
+            if let Some(removed) = live_entries.remove(path) {
+                log.push(WatchDifference {
+                    entry_type: removed.entry_type,
+                    difference_type: EntryDifferenceType::Removed,
+                    path: None,
+                    octets_difference: removed.octets,
+                });
+            }
+        }
+        Some(fresh) => match live_entries.get(path) {
+            None => {
+                log.push(WatchDifference {
+                    entry_type: fresh.entry_type.clone(),
+                    difference_type: EntryDifferenceType::New,
+                    path: Some(fresh.path.clone()),
+                    octets_difference: fresh.octets,
+                });
+
+                live_entries.insert(path.to_owned(), fresh);
+            }
+            Some(previous) => {
+                if previous.octets != fresh.octets || previous.hash != fresh.hash {
+                    log.push(WatchDifference {
+                        entry_type: fresh.entry_type.clone(),
+                        difference_type: EntryDifferenceType::SizeChange,
+                        path: Some(fresh.path.clone()),
+                        octets_difference: previous.octets.abs_diff(fresh.octets),
+                    });
+
+                    live_entries.insert(path.to_owned(), fresh);
+                }
+            }
+        },
+    }
+}
+
+/// Walks `root` synchronously, reconciling every entry that passes
+/// `filters`. Used as the periodic fallback for roots the OS watcher
+/// couldn't register, so it's deliberately simple rather than routed
+/// through the worker pool.
+fn rescan_subtree(
+    root: &str,
+    filters: &CrawlFilters,
+    config: CrawlConfig,
+    live_entries: &mut HashMap<String, EntryInfo>,
+    log: &mut Vec<WatchDifference>,
+) {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = match entry.path().to_str() {
+            Some(path) => path.to_owned(),
+            None => continue,
+        };
+
+        if filters.is_excluded(&path) {
+            continue;
+        }
+
+        if entry.path().is_dir() {
+            reconcile_path(&path, config, live_entries, log);
+            rescan_subtree(&path, filters, config, live_entries, log);
+        } else if filters.extension_allowed(&path) {
+            reconcile_path(&path, config, live_entries, log);
+        }
+    }
+}
+
+fn flush_watch_log(watch_started: &str, rolling_log: &[WatchDifference], config: CrawlConfig) {
+    if rolling_log.is_empty() {
+        return;
+    }
+
+    let watch_log = WatchLog {
+        date_time: watch_started.to_owned(),
+        entries_difference: rolling_log.to_vec(),
+    };
+
+    write_json_to_file(
+        &compressed_file_name(format!("watch_{}.json", watch_started), config),
+        &watch_log,
+        config,
+    );
+}
+
+/// Fallback for when the OS watcher itself couldn't be created: re-crawls
+/// every root on a fixed interval instead of reacting to live events.
+async fn periodic_rescan_loop(
+    roots: HashSet<String>,
+    mut live_entries: HashMap<String, EntryInfo>,
+    filters: CrawlFilters,
+    config: CrawlConfig,
+) -> ! {
+    let watch_started = Local::now().format("%F_%H-%M-%S").to_string();
+    let mut rolling_log: Vec<WatchDifference> = Vec::new();
+
+    loop {
+        for root in &roots {
+            reconcile_path(root, config, &mut live_entries, &mut rolling_log);
+            rescan_subtree(root, &filters, config, &mut live_entries, &mut rolling_log);
+        }
+
+        flush_watch_log(&watch_started, &rolling_log, config);
+
+        task::sleep(WATCH_RESCAN_INTERVAL).await;
+    }
+}
+
+/// Entered after the initial crawl when `-w`/`--watch` is set. Registers
+/// an OS-level watch on every root, debounces the resulting events, and
+/// keeps a rolling `DifferenceAnalysis`-like log flushed to disk. Roots
+/// the watcher can't register (and the watcher itself, if it fails to
+/// start at all) fall back to periodic full re-crawls.
+async fn watch_mode(
+    roots: HashSet<String>,
+    entries_info: HashSet<EntryInfo>,
+    filters: CrawlFilters,
+    config: CrawlConfig,
+) {
+    let mut live_entries: HashMap<String, EntryInfo> = entries_info
+        .into_iter()
+        .map(|entry| (entry.path.clone(), entry))
+        .collect();
+
+    let (event_tx, event_rx) = std::sync::mpsc::channel();
+
+    let mut watcher = match notify::recommended_watcher(move |result| {
+        let _ = event_tx.send(result);
+    }) {
+        Ok(watcher) => watcher,
+        Err(err) => {
+            println!(
+                "Couldn't start the filesystem watcher ({}), falling back to periodic re-crawls",
+                err
+            );
+
+            periodic_rescan_loop(roots, live_entries, filters, config).await;
+        }
+    };
+
+    let mut unwatched_roots = Vec::new();
+
+    for root in &roots {
+        if let Err(err) = watcher.watch(Path::new(root), RecursiveMode::Recursive) {
+            println!(
+                "Couldn't watch {} ({}), falling back to periodic re-crawls for it",
+                root, err
+            );
+
+            unwatched_roots.push(root.clone());
+        }
+    }
+
+    // Bridge the watcher's blocking std::sync::mpsc channel onto the async side.
+    let (path_tx, mut path_rx) = mpsc::unbounded::<String>();
+
+    std::thread::spawn(move || {
+        while let Ok(result) = event_rx.recv() {
+            let Ok(event) = result else { continue };
+
+            for path in event.paths {
+                if let Some(path_str) = path.to_str() {
+                    let _ = path_tx.unbounded_send(path_str.to_owned());
+                }
+            }
+        }
+    });
+
+    let mut pending: HashMap<String, Instant> = HashMap::new();
+    let mut rolling_log: Vec<WatchDifference> = Vec::new();
+    let watch_started = Local::now().format("%F_%H-%M-%S").to_string();
+    let mut last_flush = Instant::now();
+    let mut last_fallback_rescan = Instant::now();
+
+    loop {
+        while let Ok(Some(path)) = path_rx.try_next() {
+            pending.insert(path, Instant::now());
+        }
+
+        let now = Instant::now();
+        let ready: Vec<String> = pending
+            .iter()
+            .filter(|(_, seen)| now.duration_since(**seen) >= WATCH_DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+
+        for path in ready {
+            pending.remove(&path);
+
+            if event_path_allowed(&path, &filters) {
+                reconcile_path(&path, config, &mut live_entries, &mut rolling_log);
+            }
+        }
+
+        if !unwatched_roots.is_empty()
+            && now.duration_since(last_fallback_rescan) >= WATCH_RESCAN_INTERVAL
+        {
+            for root in &unwatched_roots {
+                rescan_subtree(root, &filters, config, &mut live_entries, &mut rolling_log);
+            }
+
+            last_fallback_rescan = now;
+        }
+
+        if now.duration_since(last_flush) >= WATCH_FLUSH_INTERVAL {
+            flush_watch_log(&watch_started, &rolling_log, config);
+            last_flush = now;
+        }
+
+        task::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[cfg(test)]
+mod glob_tests {
+    use super::*;
+
+    #[test]
+    fn star_matches_within_a_segment_only() {
+        assert!(segment_match("*.tmp", "bar.tmp"));
+        assert!(!glob_match("*.tmp", "bar/baz.tmp"));
+    }
+
+    #[test]
+    fn question_mark_matches_exactly_one_character() {
+        assert!(segment_match("fil?.txt", "file.txt"));
+        assert!(!segment_match("fil?.txt", "fil.txt"));
+        assert!(!segment_match("fil?.txt", "filee.txt"));
+    }
+
+    #[test]
+    fn double_star_matches_zero_or_more_segments() {
+        assert!(glob_match("**/node_modules", "node_modules"));
+        assert!(glob_match("**/node_modules", "src/node_modules"));
+        assert!(glob_match("**/node_modules", "a/b/c/node_modules"));
+        assert!(!glob_match("**/node_modules", "node_modules/src"));
+    }
+
+    #[test]
+    fn single_star_does_not_cross_a_separator() {
+        assert!(!glob_match("*/node_modules", "a/b/node_modules"));
+        assert!(glob_match("*/node_modules", "a/node_modules"));
+    }
+
+    #[test]
+    fn bare_pattern_matches_any_path_segment_like_gitignore() {
+        // `*.tmp` with no separator isn't anchored to the root; it should
+        // exclude a nested file, not just one that happens to be at depth 1.
+        let filters = CrawlFilters {
+            excludes: vec!["*.tmp".to_owned()],
+            extensions: Vec::new(),
+        };
+
+        assert!(filters.is_excluded("C:/Users/me/AppData/bar.tmp"));
+        assert!(filters.is_excluded("bar.tmp"));
+        assert!(!filters.is_excluded("bar.txt"));
+    }
+
+    #[test]
+    fn exact_exclude_prefix_respects_path_boundaries() {
+        let filters = CrawlFilters {
+            excludes: vec!["C:/foo".to_owned()],
+            extensions: Vec::new(),
+        };
+
+        assert!(filters.is_excluded("C:/foo"));
+        assert!(filters.is_excluded("C:/foo/bar"));
+        assert!(!filters.is_excluded("C:/foobar"));
+        assert!(!filters.is_excluded("C:/foo.txt"));
+    }
 }